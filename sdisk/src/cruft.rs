@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use console::style;
+use dialoguer::{theme::ColorfulTheme, MultiSelect};
+use humansize::{format_size, BINARY};
+use walkdir::WalkDir;
+
+use crate::{confirm, dir_size, spinner, trash};
+
+/// Well-known regenerable directory names, each paired with the sibling
+/// marker file(s) that confirm it belongs to a rebuildable project (so
+/// deleting it is safe and reversible with a rebuild).
+const KNOWN_ARTIFACT_DIRS: &[(&str, &[&str])] = &[
+    ("target", &["Cargo.toml"]),
+    ("node_modules", &["package.json"]),
+    (".venv", &["pyproject.toml", "requirements.txt", "setup.py"]),
+    ("__pycache__", &["pyproject.toml", "setup.py"]),
+    (".gradle", &["build.gradle", "build.gradle.kts"]),
+    ("build", &["build.gradle", "build.gradle.kts", "setup.py"]),
+    ("dist", &["package.json", "pyproject.toml"]),
+];
+
+struct Artifact {
+    path: PathBuf,
+    marker: PathBuf,
+    size: u64,
+}
+
+/// Find regenerable build-artifact/cache directories (`target/`,
+/// `node_modules/`, etc.) that haven't been touched in `stale_days`, and
+/// let the user multi-select which to purge.
+pub fn cmd_cruft(
+    roots: Vec<PathBuf>,
+    stale_days: u64,
+    interactive: bool,
+    yes: bool,
+    dry_run: bool,
+    permanent: bool,
+) -> Result<()> {
+    for root in &roots {
+        println!("{} {}", style("Scanning for build artifacts in").bold(), root.display());
+    }
+    let pb = spinner();
+    let mut artifacts: Vec<Artifact> = Vec::new();
+    for root in &roots {
+        find_artifacts(root, stale_days, &mut artifacts);
+    }
+    pb.finish_and_clear();
+
+    if artifacts.is_empty() {
+        println!("No stale build artifacts found.");
+        return Ok(());
+    }
+
+    artifacts.sort_by_key(|a| std::cmp::Reverse(a.size));
+    for (i, a) in artifacts.iter().enumerate() {
+        println!(
+            "{:>3}. {} — {} (marker: {})",
+            i + 1,
+            format_size(a.size, BINARY),
+            a.path.display(),
+            a.marker.display()
+        );
+    }
+
+    if dry_run {
+        println!("Would {} the above directories.", trash::dry_run_label(permanent));
+        return Ok(());
+    }
+
+    if interactive {
+        let items: Vec<String> = artifacts
+            .iter()
+            .map(|a| format!("{} — {}", format_size(a.size, BINARY), a.path.display()))
+            .collect();
+        let theme = ColorfulTheme::default();
+        let selection = MultiSelect::with_theme(&theme)
+            .with_prompt("Select artifact directories to purge (space to toggle, enter to confirm)")
+            .items(&items)
+            .interact()?;
+        if selection.is_empty() {
+            return Ok(());
+        }
+        if !yes && !confirm("Delete selected artifact directories?")? {
+            println!("Aborted.");
+            return Ok(());
+        }
+        let mut batch = Vec::new();
+        for idx in selection {
+            let a = &artifacts[idx];
+            batch.push(trash::remove_one(&a.path, a.size, permanent)?);
+        }
+        trash::commit_batch(batch)?;
+        return Ok(());
+    }
+
+    if !yes && !confirm("Delete all listed artifact directories?")? {
+        println!("Aborted.");
+        return Ok(());
+    }
+    let mut batch = Vec::new();
+    for a in &artifacts {
+        batch.push(trash::remove_one(&a.path, a.size, permanent)?);
+    }
+    trash::commit_batch(batch)?;
+    Ok(())
+}
+
+/// Walk `root` looking for known artifact directory names with a matching
+/// marker in their parent. Matches are not descended into further, since
+/// nested caches inside e.g. `node_modules/` aren't independent candidates.
+fn find_artifacts(root: &Path, stale_days: u64, out: &mut Vec<Artifact>) {
+    let cutoff_secs = stale_days * 24 * 60 * 60;
+    let mut it = WalkDir::new(root).into_iter();
+    while let Some(entry) = it.next() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let Some(name) = entry.file_name().to_str() else {
+            continue;
+        };
+        let Some((_, markers)) = KNOWN_ARTIFACT_DIRS.iter().find(|(n, _)| *n == name) else {
+            continue;
+        };
+        let Some(parent) = entry.path().parent() else {
+            continue;
+        };
+        let Some(marker) = markers.iter().map(|m| parent.join(m)).find(|p| p.exists()) else {
+            continue;
+        };
+
+        let path = entry.path().to_path_buf();
+        if is_stale(&path, cutoff_secs) {
+            let size = dir_size(&path).unwrap_or(0);
+            out.push(Artifact { path, marker, size });
+        }
+        it.skip_current_dir();
+    }
+}
+
+/// True if nothing under `path` has been modified within `cutoff_secs`.
+fn is_stale(path: &Path, cutoff_secs: u64) -> bool {
+    let cutoff = SystemTime::now() - std::time::Duration::from_secs(cutoff_secs);
+    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+        if let Ok(meta) = entry.metadata() {
+            if let Ok(modified) = meta.modified() {
+                if modified > cutoff {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
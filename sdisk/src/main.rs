@@ -1,7 +1,14 @@
+mod cruft;
+mod reclaim;
+mod scan;
+mod trash;
+mod tree;
+
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use console::style;
 use dialoguer::{theme::ColorfulTheme, MultiSelect};
 use humansize::{format_size, BINARY};
@@ -9,6 +16,41 @@ use indicatif::{ProgressBar, ProgressStyle};
 use sysinfo::Disks;
 use walkdir::WalkDir;
 
+use cruft::cmd_cruft;
+use reclaim::{cmd_reclaim, parse_size};
+use tree::cmd_tree;
+
+/// How to order results in `top`/`stale`/`tree`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SortMode {
+    /// Largest first — the "whale hunt".
+    #[default]
+    Size,
+    /// Oldest first, by recursive access/modify time.
+    Date,
+}
+
+/// Sort `items` in place per `sort`/`newest_first`. `size`/`time` extract
+/// the comparison keys, so the same helper serves `top`, `stale` and
+/// `tree`'s differently-shaped result tuples.
+pub(crate) fn sort_by_mode<T>(
+    items: &mut [T],
+    sort: SortMode,
+    newest_first: bool,
+    size: impl Fn(&T) -> u64,
+    time: impl Fn(&T) -> SystemTime,
+) {
+    match sort {
+        SortMode::Size => items.sort_by_key(|i| std::cmp::Reverse(size(i))),
+        SortMode::Date => {
+            items.sort_by_key(|i| time(i));
+            if newest_first {
+                items.reverse();
+            }
+        }
+    }
+}
+
 /// sdisk: Analyze disk usage and suggest cleanups
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -31,6 +73,24 @@ struct Cli {
     #[arg(global = true, long)]
     dry_run: bool,
 
+    /// Ignore the on-disk scan cache for this run
+    #[arg(global = true, long)]
+    no_cache: bool,
+    /// Rescan from scratch and refresh the on-disk scan cache
+    #[arg(global = true, long)]
+    refresh: bool,
+
+    /// How to order results: by size (the "whale hunt") or by date
+    #[arg(global = true, long, value_enum, default_value_t = SortMode::Size)]
+    sort: SortMode,
+    /// With `--sort date`, show newest first instead of oldest first
+    #[arg(global = true, long)]
+    newest_first: bool,
+
+    /// Delete permanently instead of moving to the OS trash (only trashed items can be restored with `undo`)
+    #[arg(global = true, long)]
+    permanent: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -66,6 +126,32 @@ enum Commands {
         #[arg(value_name = "PATH")]
         paths: Vec<PathBuf>,
     },
+    /// Show a collapsible directory tree with aggregated sizes
+    Tree {
+        /// Hide branches smaller than this percentage of their parent
+        #[arg(long, default_value_t = 1.0)]
+        min_percent: f64,
+        /// Optional paths to analyze (defaults to CWD if none and no --path)
+        #[arg(value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+    /// Select stale items to delete until a free-space goal is reached
+    Reclaim {
+        /// Desired total free space, e.g. "20GiB" or "512MB"
+        #[arg(long)]
+        target_free: String,
+        /// Optional paths to analyze (defaults to CWD if none and no --path)
+        #[arg(value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+    /// Find and purge stale build-artifact/cache directories (target/, node_modules/, ...)
+    Cruft {
+        /// Optional paths to analyze (defaults to CWD if none and no --path)
+        #[arg(value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+    /// Restore the most recent batch of trashed items
+    Undo,
 }
 
 fn main() -> Result<()> {
@@ -73,10 +159,21 @@ fn main() -> Result<()> {
     match cli.command.unwrap_or(Commands::Info) {
         Commands::Info => cmd_info(),
         Commands::Top { count, paths } => {
+            let (sort, newest_first) = (cli.sort, cli.newest_first);
             let roots = collect_roots(cli.path, paths)?;
-            cmd_top(roots, count, !cli.non_interactive, cli.yes, cli.dry_run)
+            cmd_top(
+                roots,
+                count,
+                !cli.non_interactive,
+                cli.yes,
+                cli.dry_run,
+                sort,
+                newest_first,
+                cli.permanent,
+            )
         }
         Commands::Stale { limit, paths } | Commands::Clean { limit, paths } => {
+            let (sort, newest_first) = (cli.sort, cli.newest_first);
             let roots = collect_roots(cli.path, paths)?;
             cmd_stale(
                 roots,
@@ -85,8 +182,52 @@ fn main() -> Result<()> {
                 !cli.non_interactive,
                 !cli.yes,
                 cli.dry_run,
+                cli.no_cache,
+                cli.refresh,
+                sort,
+                newest_first,
+                cli.permanent,
+            )
+        }
+        Commands::Tree { min_percent, paths } => {
+            let (sort, newest_first) = (cli.sort, cli.newest_first);
+            let roots = collect_roots(cli.path, paths)?;
+            cmd_tree(
+                roots,
+                min_percent,
+                sort,
+                newest_first,
+                cli.no_cache,
+                cli.refresh,
+            )
+        }
+        Commands::Reclaim { target_free, paths } => {
+            let roots = collect_roots(cli.path, paths)?;
+            let target_free = parse_size(&target_free)?;
+            cmd_reclaim(
+                roots,
+                cli.stale_days,
+                target_free,
+                !cli.non_interactive,
+                cli.yes,
+                cli.dry_run,
+                cli.no_cache,
+                cli.refresh,
+                cli.permanent,
+            )
+        }
+        Commands::Cruft { paths } => {
+            let roots = collect_roots(cli.path, paths)?;
+            cmd_cruft(
+                roots,
+                cli.stale_days,
+                !cli.non_interactive,
+                cli.yes,
+                cli.dry_run,
+                cli.permanent,
             )
         }
+        Commands::Undo => trash::undo_last(),
     }
 }
 
@@ -122,18 +263,26 @@ fn collect_roots(opt_root: Option<PathBuf>, extra: Vec<PathBuf>) -> Result<Vec<P
     Ok(roots)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_top(
     roots: Vec<PathBuf>,
     count: usize,
     interactive: bool,
     yes: bool,
     dry_run: bool,
+    sort: SortMode,
+    newest_first: bool,
+    permanent: bool,
 ) -> Result<()> {
     for root in &roots {
         println!("{} {}", style("Scanning").bold(), root.display());
     }
     let pb = spinner();
-    let mut entries: Vec<(PathBuf, u64)> = Vec::new();
+    // Intentionally a plain shallow walk rather than the cached recursive
+    // `scan::scan` the other commands share: `top` only ever looks at the
+    // first few levels, so a full recursive scan of the whole tree would
+    // cost far more than it returns here.
+    let mut entries: Vec<(PathBuf, u64, SystemTime)> = Vec::new();
     for root in &roots {
         for entry in WalkDir::new(root)
             .max_depth(3)
@@ -142,16 +291,20 @@ fn cmd_top(
         {
             let path = entry.path();
             if path.is_file() {
-                if let Ok(meta) = path.metadata() {
-                    entries.push((path.to_path_buf(), meta.len()));
+                if let Ok(meta) = entry.metadata() {
+                    let time = meta
+                        .accessed()
+                        .or_else(|_| meta.modified())
+                        .unwrap_or(std::time::UNIX_EPOCH);
+                    entries.push((path.to_path_buf(), meta.len(), time));
                 }
             }
         }
     }
     pb.finish_and_clear();
-    entries.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
-    let entries: Vec<(PathBuf, u64)> = entries.into_iter().take(count).collect();
-    for (i, (path, size)) in entries.iter().enumerate() {
+    sort_by_mode(&mut entries, sort, newest_first, |(_, s, _)| *s, |(_, _, t)| *t);
+    let entries: Vec<(PathBuf, u64, SystemTime)> = entries.into_iter().take(count).collect();
+    for (i, (path, size, _)) in entries.iter().enumerate() {
         println!(
             "{:>3}. {} — {}",
             i + 1,
@@ -162,7 +315,7 @@ fn cmd_top(
     if interactive && !entries.is_empty() {
         let items: Vec<String> = entries
             .iter()
-            .map(|(p, s)| format!("{} — {}", format_size(*s, BINARY), p.display()))
+            .map(|(p, s, _)| format!("{} — {}", format_size(*s, BINARY), p.display()))
             .collect();
         let theme = ColorfulTheme::default();
         let selection = MultiSelect::with_theme(&theme)
@@ -173,7 +326,7 @@ fn cmd_top(
             return Ok(());
         }
         if dry_run {
-            println!("Would remove:");
+            println!("Would {}:", trash::dry_run_label(permanent));
             for idx in selection {
                 println!("- {}", entries[idx].0.display());
             }
@@ -183,21 +336,42 @@ fn cmd_top(
             println!("Aborted.");
             return Ok(());
         }
+        let mut batch = Vec::new();
         for idx in selection {
-            let path = &entries[idx].0;
-            if path.is_file() {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("removing file {}", path.display()))?;
-            } else {
-                std::fs::remove_dir_all(path)
-                    .with_context(|| format!("removing directory {}", path.display()))?;
-            }
-            println!("Removed {}", path.display());
+            let (path, size, _) = &entries[idx];
+            batch.push(trash::remove_one(path, *size, permanent)?);
         }
+        trash::commit_batch(batch)?;
     }
     Ok(())
 }
 
+/// Collect every file/dir under `roots` whose last access (falling back to
+/// last modified) is at or before `days` ago — for a directory, the newest
+/// such time among itself and everything under it — via the shared parallel,
+/// cached scan engine. Used by both `cmd_stale` and
+/// `reclaim::cmd_reclaim`, which need the same stale candidate set.
+pub(crate) fn collect_stale(
+    roots: &[PathBuf],
+    days: u64,
+    no_cache: bool,
+    refresh: bool,
+) -> Vec<(PathBuf, u64, std::time::SystemTime)> {
+    use std::time::Duration;
+
+    let cutoff = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    let pb = spinner();
+    let scanned = scan::scan(roots, no_cache, refresh);
+    let items: Vec<(PathBuf, u64, SystemTime)> = scanned
+        .iter()
+        .filter(|(_, entry)| entry.recursive_time <= cutoff)
+        .map(|(path, entry)| (path.clone(), entry.size, entry.recursive_time))
+        .collect();
+    pb.finish_and_clear();
+    items
+}
+
+#[allow(clippy::too_many_arguments)]
 fn cmd_stale(
     roots: Vec<PathBuf>,
     days: u64,
@@ -205,10 +379,12 @@ fn cmd_stale(
     interactive: bool,
     prompt: bool,
     dry_run: bool,
+    no_cache: bool,
+    refresh: bool,
+    sort: SortMode,
+    newest_first: bool,
+    permanent: bool,
 ) -> Result<()> {
-    use std::time::{Duration, SystemTime};
-
-    let cutoff = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
     for root in &roots {
         println!(
             "{} {} (older than {} days)",
@@ -217,32 +393,8 @@ fn cmd_stale(
             days
         );
     }
-    let pb = spinner();
-    let mut items: Vec<(PathBuf, u64, SystemTime)> = Vec::new();
-    for root in &roots {
-        for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path().to_path_buf();
-            if let Ok(meta) = path.symlink_metadata() {
-                // Prefer last access; fall back to modified
-                let time = meta
-                    .accessed()
-                    .ok()
-                    .or_else(|| meta.modified().ok())
-                    .unwrap_or(SystemTime::UNIX_EPOCH);
-                if time <= cutoff {
-                    let size = if meta.is_file() {
-                        meta.len()
-                    } else {
-                        dir_size(&path).unwrap_or(0)
-                    };
-                    items.push((path, size, time));
-                }
-            }
-        }
-    }
-    pb.finish_and_clear();
-    // Largest first
-    items.sort_by_key(|(_, size, _)| std::cmp::Reverse(*size));
+    let mut items = collect_stale(&roots, days, no_cache, refresh);
+    sort_by_mode(&mut items, sort, newest_first, |(_, s, _)| *s, |(_, _, t)| *t);
     let items = items.into_iter().take(limit).collect::<Vec<_>>();
     for (i, (path, size, time)) in items.iter().enumerate() {
         let age_days = SystemTime::now()
@@ -259,7 +411,11 @@ fn cmd_stale(
         );
     }
 
-    if dry_run || items.is_empty() {
+    if dry_run {
+        println!("Would {} the above items.", trash::dry_run_label(permanent));
+        return Ok(());
+    }
+    if items.is_empty() {
         return Ok(());
     }
 
@@ -288,28 +444,16 @@ fn cmd_stale(
         if selection.is_empty() {
             return Ok(());
         }
-        if dry_run {
-            println!("Would remove:");
-            for idx in selection {
-                println!("- {}", items[idx].0.display());
-            }
-            return Ok(());
-        }
         if !confirm("Delete selected items?")? {
             println!("Aborted.");
             return Ok(());
         }
+        let mut batch = Vec::new();
         for idx in selection {
-            let path = &items[idx].0;
-            if path.is_file() {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("removing file {}", path.display()))?;
-            } else {
-                std::fs::remove_dir_all(path)
-                    .with_context(|| format!("removing directory {}", path.display()))?;
-            }
-            println!("Removed {}", path.display());
+            let (path, size, _) = &items[idx];
+            batch.push(trash::remove_one(path, *size, permanent)?);
         }
+        trash::commit_batch(batch)?;
         return Ok(());
     }
 
@@ -318,41 +462,54 @@ fn cmd_stale(
         return Ok(());
     }
 
-    for (path, _, _) in items {
-        if path.is_file() {
-            std::fs::remove_file(&path)
-                .with_context(|| format!("removing file {}", path.display()))?;
-        } else {
-            std::fs::remove_dir_all(&path)
-                .with_context(|| format!("removing directory {}", path.display()))?;
-        }
-        println!("Removed {}", path.display());
+    let mut batch = Vec::new();
+    for (path, size, _) in items {
+        batch.push(trash::remove_one(&path, size, permanent)?);
     }
+    trash::commit_batch(batch)?;
 
     Ok(())
 }
 
-fn spinner() -> ProgressBar {
+/// Per-user app directory for on-disk state (scan cache, trash undo
+/// journal), created with owner-only permissions — writing these under the
+/// shared, world-writable system temp dir at a predictable name would let
+/// another local user pre-stage them as a symlink.
+pub(crate) fn app_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+    let dir = base.join("sdisk");
+    let _ = std::fs::create_dir_all(&dir);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(&dir) {
+            let mut perms = meta.permissions();
+            perms.set_mode(0o700);
+            let _ = std::fs::set_permissions(&dir, perms);
+        }
+    }
+    dir
+}
+
+pub(crate) fn spinner() -> ProgressBar {
     let pb = ProgressBar::new_spinner();
     pb.set_style(ProgressStyle::with_template("{spinner} {msg}").unwrap());
     pb.enable_steady_tick(std::time::Duration::from_millis(100));
     pb
 }
 
-fn dir_size(path: &PathBuf) -> Result<u64> {
-    let mut size: u64 = 0;
-    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-        let p = entry.path();
-        if p.is_file() {
-            if let Ok(meta) = p.metadata() {
-                size = size.saturating_add(meta.len());
-            }
-        }
-    }
-    Ok(size)
+/// Recursive size of `path`, computed by the shared scan engine. Each call
+/// re-lists every directory under `path`; only unchanged files' cached
+/// entries are reused, so a call is not a constant-time lookup.
+pub(crate) fn dir_size(path: &PathBuf) -> Result<u64> {
+    let scanned = scan::scan(std::slice::from_ref(path), false, false);
+    Ok(scanned.size(path))
 }
 
-fn confirm(prompt: &str) -> Result<bool> {
+pub(crate) fn confirm(prompt: &str) -> Result<bool> {
     use std::io::{self, Write};
     print!("{} [y/N] ", prompt);
     io::stdout().flush().ok();
@@ -361,3 +518,39 @@ fn confirm(prompt: &str) -> Result<bool> {
     let trimmed = input.trim().to_lowercase();
     Ok(trimmed == "y" || trimmed == "yes")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn sort_by_mode_size_orders_largest_first() {
+        let mut items = vec![(1u64, UNIX_EPOCH), (3u64, UNIX_EPOCH), (2u64, UNIX_EPOCH)];
+        sort_by_mode(&mut items, SortMode::Size, false, |(s, _)| *s, |(_, t)| *t);
+        assert_eq!(
+            items.iter().map(|(s, _)| *s).collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn sort_by_mode_date_orders_oldest_first_unless_newest_first() {
+        let t0 = UNIX_EPOCH;
+        let t1 = UNIX_EPOCH + Duration::from_secs(100);
+        let t2 = UNIX_EPOCH + Duration::from_secs(200);
+        let mut items = vec![(1u64, t2), (2u64, t0), (3u64, t1)];
+
+        sort_by_mode(&mut items, SortMode::Date, false, |(s, _)| *s, |(_, t)| *t);
+        assert_eq!(
+            items.iter().map(|(s, _)| *s).collect::<Vec<_>>(),
+            vec![2, 3, 1]
+        );
+
+        sort_by_mode(&mut items, SortMode::Date, true, |(s, _)| *s, |(_, t)| *t);
+        assert_eq!(
+            items.iter().map(|(s, _)| *s).collect::<Vec<_>>(),
+            vec![1, 3, 2]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use console::style;
+use dialoguer::{theme::ColorfulTheme, MultiSelect};
+use humansize::{format_size, BINARY};
+use sysinfo::Disks;
+
+use crate::{collect_stale, confirm, trash};
+
+/// Free up space towards `target_free` bytes by selecting a minimal set of
+/// stale candidates: first try the smallest single item that alone covers
+/// the shortfall, falling back to a largest-first greedy fill.
+#[allow(clippy::too_many_arguments)]
+pub fn cmd_reclaim(
+    roots: Vec<PathBuf>,
+    stale_days: u64,
+    target_free: u64,
+    interactive: bool,
+    yes: bool,
+    dry_run: bool,
+    no_cache: bool,
+    refresh: bool,
+    permanent: bool,
+) -> Result<()> {
+    let mount_point = mount_point_for(&roots[0]);
+    let currently_free = current_free_space(&roots[0]);
+    println!(
+        "{} {} (target {})",
+        style("Currently free").bold(),
+        format_size(currently_free, BINARY),
+        format_size(target_free, BINARY)
+    );
+    if currently_free >= target_free {
+        println!("Already at or above the target free space.");
+        return Ok(());
+    }
+    let to_free = target_free - currently_free;
+    println!(
+        "{} {}",
+        style("Need to reclaim").bold(),
+        format_size(to_free, BINARY)
+    );
+
+    for root in &roots {
+        println!("{} {}", style("Scanning").bold(), root.display());
+    }
+    // Restrict candidates to whichever disk `currently_free`/`target_free`
+    // were computed against — a candidate on a different mount wouldn't
+    // move the needle on the figure we're trying to hit.
+    let candidates: Vec<(PathBuf, u64)> = collect_stale(&roots, stale_days, no_cache, refresh)
+        .into_iter()
+        .filter(|(path, _, _)| mount_point.as_ref().is_none_or(|mp| path.starts_with(mp)))
+        .map(|(path, size, _)| (path, size))
+        .collect();
+
+    let selected = select_candidates(candidates, to_free);
+    if selected.is_empty() {
+        println!("No combination of stale items reaches the target; nothing to do.");
+        return Ok(());
+    }
+
+    let selected_total: u64 = selected.iter().map(|(_, s)| *s).sum();
+    if selected_total < to_free {
+        println!(
+            "{} the available stale items only total {}, short of the {} still needed — the target won't be fully reached.",
+            style("Warning:").bold(),
+            format_size(selected_total, BINARY),
+            format_size(to_free, BINARY)
+        );
+    }
+    println!(
+        "{} {} item(s) totalling {} (projected free: {})",
+        style("Selected").bold(),
+        selected.len(),
+        format_size(selected_total, BINARY),
+        format_size(currently_free + selected_total, BINARY)
+    );
+    for (path, size) in &selected {
+        println!("  {} — {}", format_size(*size, BINARY), path.display());
+    }
+
+    let chosen: Vec<(PathBuf, u64)> = if interactive {
+        let items: Vec<String> = selected
+            .iter()
+            .map(|(p, s)| format!("{} — {}", format_size(*s, BINARY), p.display()))
+            .collect();
+        let theme = ColorfulTheme::default();
+        let chosen_idx = MultiSelect::with_theme(&theme)
+            .with_prompt("Select items to delete (space to toggle, enter to confirm)")
+            .items(&items)
+            .defaults(&vec![true; items.len()])
+            .interact()?;
+        chosen_idx.into_iter().map(|i| selected[i].clone()).collect()
+    } else {
+        selected
+    };
+
+    if chosen.is_empty() {
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("Would {}:", trash::dry_run_label(permanent));
+        for (path, _) in &chosen {
+            println!("- {}", path.display());
+        }
+        return Ok(());
+    }
+
+    if !yes && !confirm("Delete selected items?")? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut batch = Vec::new();
+    for (path, size) in &chosen {
+        batch.push(trash::remove_one(path, *size, permanent)?);
+    }
+    trash::commit_batch(batch)?;
+    Ok(())
+}
+
+/// Prefer the single smallest candidate that alone satisfies `to_free`
+/// (found via a binary search over sizes sorted ascending). If no single
+/// item is large enough, fall back to a largest-first greedy fill.
+fn select_candidates(mut candidates: Vec<(PathBuf, u64)>, to_free: u64) -> Vec<(PathBuf, u64)> {
+    candidates.sort_by_key(|(_, size)| *size);
+    let sizes: Vec<u64> = candidates.iter().map(|(_, s)| *s).collect();
+    let Ok(i) | Err(i) = sizes.binary_search(&to_free);
+    if i < candidates.len() {
+        return vec![candidates[i].clone()];
+    }
+
+    candidates.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    let mut picked = Vec::new();
+    let mut sum = 0u64;
+    for item in candidates {
+        if sum >= to_free {
+            break;
+        }
+        sum += item.1;
+        picked.push(item);
+    }
+    picked
+}
+
+/// Parse a human-readable size like `20GiB`, `20G` or a bare byte count
+/// into bytes, for the `--target-free` CLI argument.
+pub fn parse_size(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split_at = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split_at);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("invalid size '{input}'"))?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" | "kib" => 1024.0,
+        "m" | "mb" | "mib" => 1024.0 * 1024.0,
+        "g" | "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" | "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        other => return Err(anyhow!("unknown size unit '{other}' in '{input}'")),
+    };
+    Ok((number * multiplier) as u64)
+}
+
+/// Available space on the disk mounted closest to (i.e. the longest
+/// matching mount point for) `root`, reusing the same `sysinfo::Disks`
+/// source as `cmd_info`.
+fn current_free_space(root: &Path) -> u64 {
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| root.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+        .unwrap_or(0)
+}
+
+/// The mount point of the disk `root` lives on (the longest matching
+/// prefix among mounted disks), used to keep reclaim candidates on the
+/// same disk `current_free_space`/`target_free` refer to.
+fn mount_point_for(root: &Path) -> Option<PathBuf> {
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| root.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.mount_point().to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_handles_units_and_bare_bytes() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("20GiB").unwrap(), 20 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("1.5M").unwrap(), (1.5 * 1024.0 * 1024.0) as u64);
+        assert!(parse_size("5xyz").is_err());
+    }
+
+    #[test]
+    fn select_candidates_prefers_single_smallest_exact_fit() {
+        let candidates = vec![
+            (PathBuf::from("a"), 10),
+            (PathBuf::from("b"), 50),
+            (PathBuf::from("c"), 100),
+        ];
+        let picked = select_candidates(candidates, 50);
+        assert_eq!(picked, vec![(PathBuf::from("b"), 50)]);
+    }
+
+    #[test]
+    fn select_candidates_falls_back_to_greedy_fill() {
+        let candidates = vec![
+            (PathBuf::from("a"), 10),
+            (PathBuf::from("b"), 20),
+            (PathBuf::from("c"), 30),
+        ];
+        let picked = select_candidates(candidates, 45);
+        let total: u64 = picked.iter().map(|(_, s)| *s).sum();
+        assert!(total >= 45, "picked set {picked:?} doesn't reach the target");
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Per-path result of a scan: recursive size for directories (own length
+/// for files); `time`, the path's own last-access time (falling back to
+/// last modified); and `recursive_time`, the newest `time` among the path
+/// itself and everything under it — a directory full of freshly-touched
+/// files reads as recently used even though the directory inode itself
+/// wasn't touched.
+#[derive(Clone, Copy)]
+pub struct ScanEntry {
+    pub size: u64,
+    pub time: SystemTime,
+    pub recursive_time: SystemTime,
+    pub is_dir: bool,
+}
+
+/// On-disk form of a `ScanEntry`, plus the path's own last-modified time at
+/// scan time. A file's cached entry is reused only while that time is
+/// unchanged; directory entries are never read back from the cache.
+#[derive(Serialize, Deserialize)]
+struct CachedEntry {
+    size: u64,
+    time_secs: u64,
+    recursive_time_secs: u64,
+    is_dir: bool,
+    dir_mtime_secs: u64,
+}
+
+/// Aggregated result of scanning one or more roots: every file and
+/// directory encountered, keyed by its path.
+pub struct Scan {
+    entries: HashMap<PathBuf, ScanEntry>,
+}
+
+/// Per-path scan output collected by `scan_dir`: `(path, entry,
+/// dir_mtime_secs)` for a path plus everything recursed into.
+type ScanResults = Vec<(PathBuf, ScanEntry, u64)>;
+
+impl Scan {
+    pub fn size(&self, path: &Path) -> u64 {
+        self.entries.get(path).map(|e| e.size).unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &ScanEntry)> {
+        self.entries.iter()
+    }
+}
+
+/// Scan `roots` in parallel (fanning out across directory entries with
+/// rayon), computing each directory's aggregated size in a single
+/// post-order pass instead of the repeated nested walks `dir_size` used to
+/// require. Every directory is still listed on every call — that's what
+/// catches a change two or more levels below an otherwise-unchanged
+/// ancestor — but a file whose mtime still matches the on-disk cache skips
+/// re-reading its access time, which is the expensive part on most
+/// filesystems. Disable with `no_cache`/`refresh`.
+pub fn scan(roots: &[PathBuf], no_cache: bool, refresh: bool) -> Scan {
+    let mut cache = if no_cache || refresh {
+        HashMap::new()
+    } else {
+        load_cache()
+    };
+
+    let mut entries = HashMap::new();
+    for root in roots {
+        let mut out = Vec::new();
+        scan_dir(root, &cache, no_cache, &mut out);
+        for (path, entry, dir_mtime_secs) in out {
+            cache.insert(
+                path.clone(),
+                CachedEntry {
+                    size: entry.size,
+                    time_secs: to_secs(entry.time),
+                    recursive_time_secs: to_secs(entry.recursive_time),
+                    is_dir: entry.is_dir,
+                    dir_mtime_secs,
+                },
+            );
+            entries.insert(path, entry);
+        }
+    }
+
+    if !no_cache {
+        save_cache(&cache);
+    }
+    Scan { entries }
+}
+
+/// Recursively scan `path`, pushing `(path, entry, dir_mtime)` for itself
+/// and every descendant into `out`. Directories are always listed fresh —
+/// trusting a directory's own mtime for its whole subtree would miss a
+/// change two or more levels down that never bubbles up to it — but a file
+/// whose mtime still matches the cached value is served from cache instead
+/// of re-reading its access time.
+fn scan_dir(
+    path: &Path,
+    cache: &HashMap<PathBuf, CachedEntry>,
+    no_cache: bool,
+    out: &mut ScanResults,
+) -> ScanEntry {
+    let dir_mtime_secs = mtime_secs(path);
+    let self_time = own_time(path);
+
+    let Ok(read) = fs::read_dir(path) else {
+        let entry = ScanEntry {
+            size: 0,
+            time: self_time,
+            recursive_time: self_time,
+            is_dir: true,
+        };
+        out.push((path.to_path_buf(), entry, dir_mtime_secs));
+        return entry;
+    };
+    let dir_entries: Vec<_> = read.filter_map(|e| e.ok()).collect();
+
+    let results: Vec<(u64, SystemTime, ScanResults)> = dir_entries
+        .par_iter()
+        .map(|dir_entry| {
+            let child = dir_entry.path();
+            match dir_entry.file_type() {
+                Ok(ft) if ft.is_dir() => {
+                    let mut sub = Vec::new();
+                    let entry = scan_dir(&child, cache, no_cache, &mut sub);
+                    (entry.size, entry.recursive_time, sub)
+                }
+                Ok(ft) if ft.is_file() => {
+                    let meta = dir_entry.metadata().ok();
+                    let file_mtime_secs = mtime_secs_of(&meta);
+                    if !no_cache {
+                        if let Some(cached) = cache.get(&child) {
+                            if cached.dir_mtime_secs == file_mtime_secs {
+                                let entry = ScanEntry {
+                                    size: cached.size,
+                                    time: from_secs(cached.time_secs),
+                                    recursive_time: from_secs(cached.recursive_time_secs),
+                                    is_dir: false,
+                                };
+                                return (
+                                    entry.size,
+                                    entry.recursive_time,
+                                    vec![(child, entry, file_mtime_secs)],
+                                );
+                            }
+                        }
+                    }
+                    let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+                    let time = own_time(&child);
+                    let entry = ScanEntry {
+                        size,
+                        time,
+                        recursive_time: time,
+                        is_dir: false,
+                    };
+                    (size, time, vec![(child, entry, file_mtime_secs)])
+                }
+                _ => (0, UNIX_EPOCH, Vec::new()),
+            }
+        })
+        .collect();
+
+    let mut size = 0u64;
+    let mut recursive_time = self_time;
+    for (s, t, sub) in results {
+        size += s;
+        if t > recursive_time {
+            recursive_time = t;
+        }
+        out.extend(sub);
+    }
+
+    let entry = ScanEntry {
+        size,
+        time: self_time,
+        recursive_time,
+        is_dir: true,
+    };
+    out.push((path.to_path_buf(), entry, dir_mtime_secs));
+    entry
+}
+
+/// The last-access time (falling back to last-modified) for `path`,
+/// matching the staleness notion the rest of the tool uses.
+fn own_time(path: &Path) -> SystemTime {
+    fs::symlink_metadata(path)
+        .ok()
+        .and_then(|m| m.accessed().ok().or_else(|| m.modified().ok()))
+        .unwrap_or(UNIX_EPOCH)
+}
+
+fn mtime_secs(path: &Path) -> u64 {
+    fs::symlink_metadata(path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .map(to_secs)
+        .unwrap_or(0)
+}
+
+fn mtime_secs_of(meta: &Option<fs::Metadata>) -> u64 {
+    meta.as_ref()
+        .and_then(|m| m.modified().ok())
+        .map(to_secs)
+        .unwrap_or(0)
+}
+
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn from_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// Lives in the per-user app directory rather than the shared, world-
+/// writable system temp dir, so another local user can't symlink-attack a
+/// predictable path.
+fn cache_file() -> PathBuf {
+    crate::app_dir().join("scan-cache.json")
+}
+
+fn load_cache() -> HashMap<PathBuf, CachedEntry> {
+    fs::read_to_string(cache_file())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &HashMap<PathBuf, CachedEntry>) {
+    if let Ok(json) = serde_json::to_string(cache) {
+        let _ = fs::write(cache_file(), json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a small real tree under the system temp dir, unique per test
+    /// run, so scanning it exercises actual filesystem metadata.
+    fn temp_tree(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "sdisk-scan-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), b"hello").unwrap();
+        fs::write(dir.join("sub").join("b.txt"), b"world").unwrap();
+        dir
+    }
+
+    #[test]
+    fn cache_hit_still_reports_every_descendant() {
+        let root = temp_tree("cache-roundtrip");
+        let roots = vec![root.clone()];
+
+        let first = scan(&roots, false, false);
+        assert!(first.iter().any(|(p, _)| p.ends_with("a.txt")));
+        assert!(first.iter().any(|(p, _)| p.ends_with("b.txt")));
+
+        // Second call with caching enabled should serve the unchanged
+        // `a.txt`/`b.txt` from the on-disk cache, and must still report
+        // every file beneath `root`/`sub`, not just the directories' rollups.
+        let second = scan(&roots, false, false);
+        let paths: Vec<PathBuf> = second.iter().map(|(p, _)| p.clone()).collect();
+        assert!(
+            paths.iter().any(|p| p.ends_with("a.txt")),
+            "missing a.txt from cached scan: {paths:?}"
+        );
+        assert!(
+            paths.iter().any(|p| p.ends_with("b.txt")),
+            "missing b.txt from cached scan: {paths:?}"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// One item removed by a command invocation, enough to restore it (if
+/// trashed) or just to report it's gone for good (if `--permanent`).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    original_path: PathBuf,
+    size: u64,
+    /// Identifies the OS trash entry to restore, `None` for a permanent
+    /// removal (which `undo` can't bring back).
+    trash_item: Option<TrashItemKey>,
+}
+
+/// The fields of a `trash::os_limited::TrashItem` that uniquely identify
+/// it, so we can re-find it in the OS trash list at `undo` time without
+/// needing `TrashItem` itself to be (de)serializable.
+#[derive(Serialize, Deserialize, Clone)]
+struct TrashItemKey {
+    name: String,
+    original_parent: PathBuf,
+    time_deleted: i64,
+}
+
+/// The on-disk undo journal: every committed batch, most recent last.
+#[derive(Serialize, Deserialize, Default)]
+struct Journal {
+    batches: Vec<Batch>,
+}
+
+/// One command invocation's worth of removals — the unit `undo` restores.
+#[derive(Serialize, Deserialize)]
+struct Batch {
+    timestamp_secs: u64,
+    entries: Vec<JournalEntry>,
+}
+
+/// Remove `path`, trashing it via the OS recycle bin unless `permanent`,
+/// and return the journal entry describing what happened so the caller
+/// can collect entries across a whole command invocation and pass them
+/// to [`commit_batch`] together as one undoable unit.
+pub fn remove_one(path: &Path, size: u64, permanent: bool) -> Result<JournalEntry> {
+    if permanent {
+        if path.is_file() {
+            fs::remove_file(path)
+                .with_context(|| format!("removing file {}", path.display()))?;
+        } else {
+            fs::remove_dir_all(path)
+                .with_context(|| format!("removing directory {}", path.display()))?;
+        }
+        println!("Removed {}", path.display());
+        return Ok(JournalEntry {
+            original_path: path.to_path_buf(),
+            size,
+            trash_item: None,
+        });
+    }
+
+    trash::delete(path).with_context(|| format!("trashing {}", path.display()))?;
+    println!("Trashed {}", path.display());
+    let trash_item = find_trash_item(path);
+    Ok(JournalEntry {
+        original_path: path.to_path_buf(),
+        size,
+        trash_item,
+    })
+}
+
+/// Best-effort lookup of the OS trash entry `trash::delete` just created
+/// for `path`, so `undo` can restore exactly that entry later. `delete`
+/// doesn't hand back an identifier, so we re-list the trash and match on
+/// name/original parent, taking the most recently deleted match.
+fn find_trash_item(path: &Path) -> Option<TrashItemKey> {
+    let name = path.file_name()?.to_str()?.to_string();
+    let parent = path.parent()?.to_path_buf();
+    trash::os_limited::list()
+        .ok()?
+        .into_iter()
+        .filter(|item| item.name == name && item.original_parent == parent)
+        .max_by_key(|item| item.time_deleted)
+        .map(|item| TrashItemKey {
+            name: item.name,
+            original_parent: item.original_parent,
+            time_deleted: item.time_deleted,
+        })
+}
+
+/// Describe, for `--dry-run`, whether items would be trashed or
+/// permanently removed.
+pub fn dry_run_label(permanent: bool) -> &'static str {
+    if permanent {
+        "permanently remove"
+    } else {
+        "trash"
+    }
+}
+
+/// Append one batch of removals (everything one command invocation
+/// removed) to the on-disk undo journal.
+pub fn commit_batch(entries: Vec<JournalEntry>) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut journal = load_journal();
+    journal.batches.push(Batch {
+        timestamp_secs: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        entries,
+    });
+    save_journal(&journal)
+}
+
+/// Restore every item in the most recent removal batch. Items that were
+/// removed with `--permanent` are reported as un-restorable, since they
+/// were never trashed in the first place. Entries whose restore fails stay
+/// in the journal as a new batch so a retry (or a later `undo`) can still
+/// find them, instead of being silently dropped.
+pub fn undo_last() -> Result<()> {
+    let mut journal = load_journal();
+    let Some(batch) = journal.batches.pop() else {
+        println!("Nothing to undo.");
+        return Ok(());
+    };
+
+    let mut failed = Vec::new();
+    for entry in batch.entries {
+        let Some(key) = &entry.trash_item else {
+            println!(
+                "Cannot restore {} — it was permanently removed",
+                entry.original_path.display()
+            );
+            continue;
+        };
+        let Some(item) = trash::os_limited::list()
+            .ok()
+            .into_iter()
+            .flatten()
+            .find(|item| item.name == key.name && item.original_parent == key.original_parent && item.time_deleted == key.time_deleted)
+        else {
+            println!(
+                "Cannot restore {} — it's no longer in the trash",
+                entry.original_path.display()
+            );
+            continue;
+        };
+        match trash::os_limited::restore_all([item]) {
+            Ok(()) => println!("Restored {}", entry.original_path.display()),
+            Err(e) => {
+                println!("Failed to restore {}: {e}", entry.original_path.display());
+                failed.push(entry);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        journal.batches.push(Batch {
+            timestamp_secs: batch.timestamp_secs,
+            entries: failed,
+        });
+    }
+
+    save_journal(&journal)
+}
+
+/// Lives in the per-user app directory rather than the shared, world-
+/// writable system temp dir, so another local user can't symlink-attack a
+/// predictable path and corrupt or redirect the undo journal.
+fn journal_file() -> PathBuf {
+    crate::app_dir().join("trash-journal.json")
+}
+
+fn load_journal() -> Journal {
+    fs::read_to_string(journal_file())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_journal(journal: &Journal) -> Result<()> {
+    let json = serde_json::to_string(journal).context("serializing trash journal")?;
+    fs::write(journal_file(), json).context("writing trash journal")
+}
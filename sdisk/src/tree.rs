@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use console::style;
+use humansize::{format_size, BINARY};
+
+use crate::{scan, sort_by_mode, SortMode};
+
+/// Collapsible directory tree renderer, used by `sdisk tree` to show which
+/// *directories* are consuming space rather than which individual files.
+/// Recursive sizes and recency come straight from the shared scan engine
+/// (`scan::scan`), not a separate walk.
+pub fn cmd_tree(
+    roots: Vec<PathBuf>,
+    min_percent: f64,
+    sort: SortMode,
+    newest_first: bool,
+    no_cache: bool,
+    refresh: bool,
+) -> Result<()> {
+    for root in &roots {
+        println!("{} {}", style("Scanning").bold(), root.display());
+    }
+    let scanned = scan::scan(&roots, no_cache, refresh);
+    let (sizes, times) = dir_stats(&scanned);
+    for root in &roots {
+        let total = *sizes.get(root).unwrap_or(&0);
+        render(root, total, 0, &sizes, &times, min_percent, sort, newest_first);
+    }
+    Ok(())
+}
+
+/// Pull each directory's recursive size and recency straight out of the
+/// shared scan — already aggregated bottom-up by `scan::scan` — keyed for
+/// `render`'s lookups.
+fn dir_stats(scanned: &scan::Scan) -> (HashMap<PathBuf, u64>, HashMap<PathBuf, SystemTime>) {
+    let mut sizes: HashMap<PathBuf, u64> = HashMap::new();
+    let mut times: HashMap<PathBuf, SystemTime> = HashMap::new();
+    for (path, entry) in scanned.iter() {
+        if entry.is_dir {
+            sizes.insert(path.clone(), entry.size);
+            times.insert(path.clone(), entry.recursive_time);
+        }
+    }
+    (sizes, times)
+}
+
+/// Print one directory and recurse into its children per `sort`, pruning
+/// branches smaller than `min_percent` of their parent's total.
+#[allow(clippy::too_many_arguments)]
+fn render(
+    path: &Path,
+    size: u64,
+    depth: usize,
+    sizes: &HashMap<PathBuf, u64>,
+    times: &HashMap<PathBuf, SystemTime>,
+    min_percent: f64,
+    sort: SortMode,
+    newest_first: bool,
+) {
+    println!(
+        "{}{} — {}",
+        "  ".repeat(depth),
+        format_size(size, BINARY),
+        path.display()
+    );
+
+    let Ok(read_dir) = fs::read_dir(path) else {
+        return;
+    };
+    let mut children: Vec<(PathBuf, u64, SystemTime)> = read_dir
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let p = e.path();
+            let s = *sizes.get(&p).unwrap_or(&0);
+            let t = *times.get(&p).unwrap_or(&SystemTime::UNIX_EPOCH);
+            (p, s, t)
+        })
+        .collect();
+    sort_by_mode(&mut children, sort, newest_first, |(_, s, _)| *s, |(_, _, t)| *t);
+
+    for (child, child_size, _) in children {
+        if size > 0 && (child_size as f64 / size as f64) * 100.0 < min_percent {
+            continue;
+        }
+        render(
+            &child,
+            child_size,
+            depth + 1,
+            sizes,
+            times,
+            min_percent,
+            sort,
+            newest_first,
+        );
+    }
+}